@@ -0,0 +1,35 @@
+use std::fmt;
+
+/// Errors that can occur while parsing an ELF file: the `e_ident` array
+/// itself, or anything parsed on top of it (the rest of the header, the
+/// program header table, `PT_NOTE` segments).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ElfError {
+    /// A read ran past the end of the buffer, e.g. a truncated file, or an
+    /// `e_phoff`/`e_shoff`/note offset and size that don't fit within it.
+    ShortRead,
+    /// The first 4 bytes were not the ELF magic number.
+    BadMagic([u8; 4]),
+    /// `EI_CLASS` held a value other than `ELFCLASS32`/`ELFCLASS64`.
+    UnknownClass(u8),
+    /// `EI_DATA` held a value other than `ELFDATA2LSB`/`ELFDATA2MSB`.
+    UnknownData(u8),
+    /// `EI_VERSION` held a value other than `EV_CURRENT`.
+    UnknownVersion(u8),
+}
+
+impl fmt::Display for ElfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ElfError::ShortRead => write!(f, "unexpected end of data while reading ELF file"),
+            ElfError::BadMagic(magic) => write!(f, "unknown or bad magic number: {:02X?}", magic),
+            ElfError::UnknownClass(class) => write!(f, "invalid ELF class `0x{:x}`", class),
+            ElfError::UnknownData(data) => write!(f, "unknown ELF data format `0x{:x}`", data),
+            ElfError::UnknownVersion(version) => {
+                write!(f, "unknown ELF version `0x{:x}`", version)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ElfError {}
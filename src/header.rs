@@ -0,0 +1,361 @@
+//! Parsing of the full ELF header (`Elf32_Ehdr`/`Elf64_Ehdr`), beyond the
+//! 16-byte `e_ident` array.
+
+use std::convert::TryInto;
+
+use crate::error::ElfError;
+use crate::ident::{parse_ident, ElfClass, ElfData, ElfIdent, EI_NIDENT};
+
+/// No file type.
+pub const ET_NONE: u16 = 0;
+
+/// Relocatable file.
+pub const ET_REL: u16 = 1;
+
+/// Executable file.
+pub const ET_EXEC: u16 = 2;
+
+/// Shared object file.
+pub const ET_DYN: u16 = 3;
+
+/// Core file.
+pub const ET_CORE: u16 = 4;
+
+/// Returns a readelf-style description of `e_type`, e.g. `"EXEC (Executable file)"`.
+pub fn type_description(e_type: u16) -> String {
+    match e_type {
+        ET_NONE => "NONE (No file type)".to_string(),
+        ET_REL => "REL (Relocatable file)".to_string(),
+        ET_EXEC => "EXEC (Executable file)".to_string(),
+        ET_DYN => "DYN (Shared object file)".to_string(),
+        ET_CORE => "CORE (Core file)".to_string(),
+        other => format!("<unknown>: 0x{:x}", other),
+    }
+}
+
+/// A parsed `Elf32_Ehdr`/`Elf64_Ehdr`: the decoded `e_ident` plus the fields
+/// that follow it. Address and offset fields are widened to `u64` regardless
+/// of `ElfClass`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ElfHeader {
+    pub ident: ElfIdent,
+    pub e_type: u16,
+    pub e_machine: u16,
+    pub e_version: u32,
+    pub e_entry: u64,
+    pub e_phoff: u64,
+    pub e_shoff: u64,
+    pub e_flags: u32,
+    pub e_ehsize: u16,
+    pub e_phentsize: u16,
+    pub e_phnum: u16,
+    pub e_shentsize: u16,
+    pub e_shnum: u16,
+    pub e_shstrndx: u16,
+}
+
+/// No segment.
+pub const PT_NULL: u32 = 0;
+
+/// Loadable segment.
+pub const PT_LOAD: u32 = 1;
+
+/// Dynamic linking information.
+pub const PT_DYNAMIC: u32 = 2;
+
+/// Program interpreter path.
+pub const PT_INTERP: u32 = 3;
+
+/// Auxiliary information, e.g. an `NT_GNU_ABI_TAG` note.
+pub const PT_NOTE: u32 = 4;
+
+/// An entry of the program header table (`Elf32_Phdr`/`Elf64_Phdr`). Address,
+/// offset, and size fields are widened to `u64` regardless of `ElfClass`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProgramHeader {
+    pub p_type: u32,
+    pub p_flags: u32,
+    pub p_offset: u64,
+    pub p_vaddr: u64,
+    pub p_paddr: u64,
+    pub p_filesz: u64,
+    pub p_memsz: u64,
+    pub p_align: u64,
+}
+
+/// Size in bytes of a single `Elf32_Phdr`/`Elf64_Phdr` entry.
+fn phentsize(class: ElfClass) -> usize {
+    match class {
+        ElfClass::Elf32 => 32,
+        ElfClass::Elf64 => 56,
+    }
+}
+
+/// Parse the program header table described by `header`.
+///
+/// `e_phoff`, `e_phentsize`, and `e_phnum` all come straight from the file
+/// and are fully attacker-controlled, so every offset is computed with
+/// checked arithmetic and bounds-checked against `buf` before any field is
+/// read out of it.
+pub fn parse_program_headers(buf: &[u8], header: &ElfHeader) -> Result<Vec<ProgramHeader>, ElfError> {
+    let data = header.ident.data;
+    let entsize = phentsize(header.ident.class);
+    let mut phdrs = Vec::with_capacity(header.e_phnum as usize);
+
+    for i in 0..header.e_phnum as usize {
+        let stride = i
+            .checked_mul(header.e_phentsize as usize)
+            .ok_or(ElfError::ShortRead)?;
+        let base = (header.e_phoff as usize)
+            .checked_add(stride)
+            .ok_or(ElfError::ShortRead)?;
+        let end = base.checked_add(entsize).ok_or(ElfError::ShortRead)?;
+        let entry = buf.get(base..end).ok_or(ElfError::ShortRead)?;
+
+        let phdr = match header.ident.class {
+            ElfClass::Elf32 => ProgramHeader {
+                p_type: read_u32(entry, 0, data)?,
+                p_offset: read_u32(entry, 4, data)? as u64,
+                p_vaddr: read_u32(entry, 8, data)? as u64,
+                p_paddr: read_u32(entry, 12, data)? as u64,
+                p_filesz: read_u32(entry, 16, data)? as u64,
+                p_memsz: read_u32(entry, 20, data)? as u64,
+                p_flags: read_u32(entry, 24, data)?,
+                p_align: read_u32(entry, 28, data)? as u64,
+            },
+            ElfClass::Elf64 => ProgramHeader {
+                p_type: read_u32(entry, 0, data)?,
+                p_flags: read_u32(entry, 4, data)?,
+                p_offset: read_u64(entry, 8, data)?,
+                p_vaddr: read_u64(entry, 16, data)?,
+                p_paddr: read_u64(entry, 24, data)?,
+                p_filesz: read_u64(entry, 32, data)?,
+                p_memsz: read_u64(entry, 40, data)?,
+                p_align: read_u64(entry, 48, data)?,
+            },
+        };
+        phdrs.push(phdr);
+    }
+
+    Ok(phdrs)
+}
+
+pub(crate) fn read_u16(buf: &[u8], off: usize, data: ElfData) -> Result<u16, ElfError> {
+    let end = off.checked_add(2).ok_or(ElfError::ShortRead)?;
+    let bytes: [u8; 2] = buf
+        .get(off..end)
+        .ok_or(ElfError::ShortRead)?
+        .try_into()
+        .map_err(|_| ElfError::ShortRead)?;
+    Ok(match data {
+        ElfData::Little => u16::from_le_bytes(bytes),
+        ElfData::Big => u16::from_be_bytes(bytes),
+    })
+}
+
+pub(crate) fn read_u32(buf: &[u8], off: usize, data: ElfData) -> Result<u32, ElfError> {
+    let end = off.checked_add(4).ok_or(ElfError::ShortRead)?;
+    let bytes: [u8; 4] = buf
+        .get(off..end)
+        .ok_or(ElfError::ShortRead)?
+        .try_into()
+        .map_err(|_| ElfError::ShortRead)?;
+    Ok(match data {
+        ElfData::Little => u32::from_le_bytes(bytes),
+        ElfData::Big => u32::from_be_bytes(bytes),
+    })
+}
+
+fn read_u64(buf: &[u8], off: usize, data: ElfData) -> Result<u64, ElfError> {
+    let end = off.checked_add(8).ok_or(ElfError::ShortRead)?;
+    let bytes: [u8; 8] = buf
+        .get(off..end)
+        .ok_or(ElfError::ShortRead)?
+        .try_into()
+        .map_err(|_| ElfError::ShortRead)?;
+    Ok(match data {
+        ElfData::Little => u64::from_le_bytes(bytes),
+        ElfData::Big => u64::from_be_bytes(bytes),
+    })
+}
+
+/// Reads an address/offset-sized field at `off`, choosing a 4- or 8-byte
+/// width based on `class` and widening the result to `u64`.
+fn read_addr(buf: &[u8], off: usize, class: ElfClass, data: ElfData) -> Result<u64, ElfError> {
+    match class {
+        ElfClass::Elf32 => read_u32(buf, off, data).map(u64::from),
+        ElfClass::Elf64 => read_u64(buf, off, data),
+    }
+}
+
+/// Parse a full ELF header out of `buf`, including the `e_ident` array.
+pub fn parse_header(buf: &[u8]) -> Result<ElfHeader, ElfError> {
+    let ident = parse_ident(buf)?;
+
+    let e_type = read_u16(buf, EI_NIDENT, ident.data)?;
+    let e_machine = read_u16(buf, EI_NIDENT + 2, ident.data)?;
+    let e_version = read_u32(buf, EI_NIDENT + 4, ident.data)?;
+
+    let addr_size = match ident.class {
+        ElfClass::Elf32 => 4,
+        ElfClass::Elf64 => 8,
+    };
+    let e_entry_off = EI_NIDENT + 8;
+    let e_phoff_off = e_entry_off + addr_size;
+    let e_shoff_off = e_phoff_off + addr_size;
+    let e_flags_off = e_shoff_off + addr_size;
+
+    let e_entry = read_addr(buf, e_entry_off, ident.class, ident.data)?;
+    let e_phoff = read_addr(buf, e_phoff_off, ident.class, ident.data)?;
+    let e_shoff = read_addr(buf, e_shoff_off, ident.class, ident.data)?;
+    let e_flags = read_u32(buf, e_flags_off, ident.data)?;
+
+    let mut off = e_flags_off + 4;
+    let e_ehsize = read_u16(buf, off, ident.data)?;
+    off += 2;
+    let e_phentsize = read_u16(buf, off, ident.data)?;
+    off += 2;
+    let e_phnum = read_u16(buf, off, ident.data)?;
+    off += 2;
+    let e_shentsize = read_u16(buf, off, ident.data)?;
+    off += 2;
+    let e_shnum = read_u16(buf, off, ident.data)?;
+    off += 2;
+    let e_shstrndx = read_u16(buf, off, ident.data)?;
+
+    Ok(ElfHeader {
+        ident,
+        e_type,
+        e_machine,
+        e_version,
+        e_entry,
+        e_phoff,
+        e_shoff,
+        e_flags,
+        e_ehsize,
+        e_phentsize,
+        e_phnum,
+        e_shentsize,
+        e_shnum,
+        e_shstrndx,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ident::{
+        ELFCLASS32, ELFCLASS64, ELFDATA2LSB, ELFDATA2MSB, ELFMAG, EV_CURRENT, EI_CLASS, EI_DATA,
+        EI_VERSION,
+    };
+
+    /// Builds a minimal, otherwise-valid `Elf32_Ehdr`/`Elf64_Ehdr` with
+    /// `e_phoff`/`e_phentsize`/`e_phnum` set to the given values, so tests
+    /// can exercise `parse_program_headers` without a real program header
+    /// table present.
+    fn build_ehdr(class: u8, data: u8, e_phoff: u64, e_phentsize: u16, e_phnum: u16) -> Vec<u8> {
+        let big = data == ELFDATA2MSB;
+        let mut buf = vec![0_u8; 16];
+        buf[..4].copy_from_slice(ELFMAG);
+        buf[EI_CLASS] = class;
+        buf[EI_DATA] = data;
+        buf[EI_VERSION] = EV_CURRENT;
+
+        let push_u16 = |buf: &mut Vec<u8>, v: u16| {
+            buf.extend_from_slice(&if big { v.to_be_bytes() } else { v.to_le_bytes() });
+        };
+        let push_u32 = |buf: &mut Vec<u8>, v: u32| {
+            buf.extend_from_slice(&if big { v.to_be_bytes() } else { v.to_le_bytes() });
+        };
+        let push_u64 = |buf: &mut Vec<u8>, v: u64| {
+            buf.extend_from_slice(&if big { v.to_be_bytes() } else { v.to_le_bytes() });
+        };
+
+        push_u16(&mut buf, ET_EXEC); // e_type
+        push_u16(&mut buf, 0x3e); // e_machine
+        push_u32(&mut buf, 1); // e_version
+
+        if class == ELFCLASS64 {
+            push_u64(&mut buf, 0x400000); // e_entry
+            push_u64(&mut buf, e_phoff); // e_phoff
+            push_u64(&mut buf, 0); // e_shoff
+        } else {
+            push_u32(&mut buf, 0x8048000); // e_entry
+            push_u32(&mut buf, e_phoff as u32); // e_phoff
+            push_u32(&mut buf, 0); // e_shoff
+        }
+
+        push_u32(&mut buf, 0); // e_flags
+        push_u16(&mut buf, 0); // e_ehsize
+        push_u16(&mut buf, e_phentsize); // e_phentsize
+        push_u16(&mut buf, e_phnum); // e_phnum
+        push_u16(&mut buf, 0); // e_shentsize
+        push_u16(&mut buf, 0); // e_shnum
+        push_u16(&mut buf, 0); // e_shstrndx
+
+        buf
+    }
+
+    #[test]
+    fn parse_header_rejects_short_buffer() {
+        let buf = build_ehdr(ELFCLASS64, ELFDATA2LSB, 64, 56, 0);
+        assert_eq!(parse_header(&buf[..32]), Err(ElfError::ShortRead));
+    }
+
+    #[test]
+    fn parse_header_reads_32bit_little_endian() {
+        let buf = build_ehdr(ELFCLASS32, ELFDATA2LSB, 52, 32, 1);
+        let header = parse_header(&buf).unwrap();
+        assert_eq!(header.ident.class, ElfClass::Elf32);
+        assert_eq!(header.ident.data, ElfData::Little);
+        assert_eq!(header.e_entry, 0x8048000);
+        assert_eq!(header.e_phoff, 52);
+        assert_eq!(header.e_phentsize, 32);
+        assert_eq!(header.e_phnum, 1);
+    }
+
+    #[test]
+    fn parse_header_reads_64bit_big_endian() {
+        let buf = build_ehdr(ELFCLASS64, ELFDATA2MSB, 64, 56, 2);
+        let header = parse_header(&buf).unwrap();
+        assert_eq!(header.ident.class, ElfClass::Elf64);
+        assert_eq!(header.ident.data, ElfData::Big);
+        assert_eq!(header.e_entry, 0x400000);
+        assert_eq!(header.e_phoff, 64);
+        assert_eq!(header.e_phentsize, 56);
+        assert_eq!(header.e_phnum, 2);
+    }
+
+    #[test]
+    fn parse_program_headers_reads_a_single_entry() {
+        let mut buf = build_ehdr(ELFCLASS64, ELFDATA2LSB, 64, 56, 1);
+        let header = parse_header(&buf).unwrap();
+        buf.extend_from_slice(&PT_LOAD.to_le_bytes()); // p_type
+        buf.extend_from_slice(&5_u32.to_le_bytes()); // p_flags
+        buf.extend_from_slice(&0_u64.to_le_bytes()); // p_offset
+        buf.extend_from_slice(&0x400000_u64.to_le_bytes()); // p_vaddr
+        buf.extend_from_slice(&0x400000_u64.to_le_bytes()); // p_paddr
+        buf.extend_from_slice(&0x1000_u64.to_le_bytes()); // p_filesz
+        buf.extend_from_slice(&0x1000_u64.to_le_bytes()); // p_memsz
+        buf.extend_from_slice(&0x1000_u64.to_le_bytes()); // p_align
+
+        let phdrs = parse_program_headers(&buf, &header).unwrap();
+        assert_eq!(phdrs.len(), 1);
+        assert_eq!(phdrs[0].p_type, PT_LOAD);
+        assert_eq!(phdrs[0].p_filesz, 0x1000);
+    }
+
+    /// Regression test for an `e_phoff` crafted so that the end of the
+    /// entry, `e_phoff + i * e_phentsize` plus the entry size, overflows
+    /// `usize`: this must return
+    /// `ElfError::ShortRead`, never panic.
+    #[test]
+    fn parse_program_headers_rejects_overflowing_phoff() {
+        let buf = build_ehdr(ELFCLASS64, ELFDATA2LSB, u64::MAX - 1, 56, 1);
+        let header = parse_header(&buf).unwrap();
+        assert_eq!(
+            parse_program_headers(&buf, &header),
+            Err(ElfError::ShortRead)
+        );
+    }
+}
@@ -0,0 +1,233 @@
+//! Parsing of the ELF identification array, `e_ident`.
+
+use crate::error::ElfError;
+
+/// The ELF magic number.
+pub const ELFMAG: &[u8; 4] = b"\x7FELF";
+
+/// Sizeof ELF magic number.
+pub const SELFMAG: usize = 4;
+
+/// Byte index identifies the architecture for this binary.
+pub const EI_CLASS: usize = 4;
+
+/// Class is invalid.
+pub const ELFCLASSNONE: u8 = 0;
+
+/// This defines the 32-bit architecture.
+pub const ELFCLASS32: u8 = 1;
+
+/// This defines the 64-bit architecture.
+pub const ELFCLASS64: u8 = 2;
+
+/// Sizeof ELF e_ident array.
+pub const EI_NIDENT: usize = 16;
+
+/// Byte index specifies the data encoding of the processor-specific data in the file.
+pub const EI_DATA: usize = 5;
+
+/// Unknown data format.
+pub const ELFDATANONE: u8 = 0;
+
+/// Two's complement, little-endian.
+pub const ELFDATA2LSB: u8 = 1;
+
+/// Two's complement, big-endian.
+pub const ELFDATA2MSB: u8 = 2;
+
+/// Byte index specifiies the version number of the ELF specification
+pub const EI_VERSION: usize = 6;
+
+/// Invalid version.
+pub const EV_NONE: u8 = 0;
+
+/// Current version (1).
+pub const EV_CURRENT: u8 = 1;
+
+/// Byte index identifies the operating system and ABI to which the object is targeted.
+/// Some fields in other ELF structures have flags and values that have
+/// platform-specific meanings; the interpretation of those fields is determined by the value of this byte.
+pub const EI_OSABI: usize = 7;
+
+/// Same as ELFOSABI_SYSV
+pub const ELFOSABI_NONE: u8 = 0;
+
+/// UNIX System V ABI.
+pub const ELFOSABI_SYSV: u8 = 0;
+
+/// HP-UX ABI
+pub const ELFOSABI_HPUX: u8 = 1;
+
+/// NetBSD ABI
+pub const ELFOSABI_NETBSD: u8 = 2;
+
+/// Linux ABI. Same as ELFOSABI_GNU.
+pub const ELFOSABI_LINUX: u8 = 3;
+
+/// Same as ELFOSABI_LINUX. Some toolchains brand GNU/Linux objects this way instead.
+pub const ELFOSABI_GNU: u8 = 3;
+
+/// Solaris ABI
+pub const ELFOSABI_SOLARIS: u8 = 6;
+
+/// AIX ABI
+pub const ELFOSABI_AIX: u8 = 7;
+
+/// IRIX ABI
+pub const ELFOSABI_IRIX: u8 = 8;
+
+/// FreeBSD ABI
+pub const ELFOSABI_FREEBSD: u8 = 9;
+
+/// Compaq TRU64 UNIX ABI
+pub const ELFOSABI_TRU64: u8 = 10;
+
+/// Novell Modesto ABI
+pub const ELFOSABI_MODESTO: u8 = 11;
+
+/// OpenBSD ABI
+pub const ELFOSABI_OPENBSD: u8 = 12;
+
+/// ARM EABI
+pub const ELFOSABI_ARM_AEABI: u8 = 64;
+
+/// ARM architecture ABI
+pub const ELFOSABI_ARM: u8 = 97;
+
+/// Stand-alone (embedded) ABI
+pub const ELFOSABI_STANDALONE: u8 = 255;
+
+/// Byte index specifices the version of the ABI to which the object is targeted.
+/// This field is used to distinguish among incompatible versions of  an  ABI.
+/// The  interpretation  of  this version number is dependent on the ABI identified by the EI_OSABI field.
+/// Applications conforming to this specification use the value 0.
+pub const EI_ABIVERSION: usize = 8;
+
+/// ELF file class, i.e. whether this is a 32-bit or 64-bit object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ElfClass {
+    Elf32,
+    Elf64,
+}
+
+/// ELF data encoding, i.e. the byte order of multi-byte fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ElfData {
+    /// Two's complement, little-endian.
+    Little,
+    /// Two's complement, big-endian.
+    Big,
+}
+
+/// The parsed `e_ident` array of an ELF file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ElfIdent {
+    pub class: ElfClass,
+    pub data: ElfData,
+    pub version: u8,
+    pub os_abi: u8,
+    pub abi_version: u8,
+}
+
+/// Parse the `e_ident` array out of the first bytes of an ELF file.
+///
+/// `buf` must contain at least `EI_NIDENT` bytes; only the first `EI_NIDENT`
+/// are inspected.
+pub fn parse_ident(buf: &[u8]) -> Result<ElfIdent, ElfError> {
+    if buf.len() < EI_NIDENT {
+        return Err(ElfError::ShortRead);
+    }
+
+    if &buf[..SELFMAG] != ELFMAG {
+        let mut magic = [0_u8; SELFMAG];
+        magic.copy_from_slice(&buf[..SELFMAG]);
+        return Err(ElfError::BadMagic(magic));
+    }
+
+    let class = match buf[EI_CLASS] {
+        ELFCLASS64 => ElfClass::Elf64,
+        ELFCLASS32 => ElfClass::Elf32,
+        other => return Err(ElfError::UnknownClass(other)),
+    };
+
+    let data = match buf[EI_DATA] {
+        ELFDATA2LSB => ElfData::Little,
+        ELFDATA2MSB => ElfData::Big,
+        other => return Err(ElfError::UnknownData(other)),
+    };
+
+    let version = buf[EI_VERSION];
+    if version != EV_CURRENT {
+        return Err(ElfError::UnknownVersion(version));
+    }
+
+    Ok(ElfIdent {
+        class,
+        data,
+        version,
+        os_abi: buf[EI_OSABI],
+        abi_version: buf[EI_ABIVERSION],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a valid, minimal `e_ident` array.
+    fn build_ident(class: u8, data: u8, version: u8, os_abi: u8, abi_version: u8) -> [u8; EI_NIDENT] {
+        let mut buf = [0_u8; EI_NIDENT];
+        buf[..SELFMAG].copy_from_slice(ELFMAG);
+        buf[EI_CLASS] = class;
+        buf[EI_DATA] = data;
+        buf[EI_VERSION] = version;
+        buf[EI_OSABI] = os_abi;
+        buf[EI_ABIVERSION] = abi_version;
+        buf
+    }
+
+    #[test]
+    fn rejects_short_buffer() {
+        let buf = build_ident(ELFCLASS64, ELFDATA2LSB, EV_CURRENT, ELFOSABI_SYSV, 0);
+        assert_eq!(parse_ident(&buf[..EI_NIDENT - 1]), Err(ElfError::ShortRead));
+    }
+
+    #[test]
+    fn rejects_bad_magic() {
+        let mut buf = build_ident(ELFCLASS64, ELFDATA2LSB, EV_CURRENT, ELFOSABI_SYSV, 0);
+        buf[0] = b'X';
+        assert_eq!(
+            parse_ident(&buf),
+            Err(ElfError::BadMagic([b'X', b'E', b'L', b'F']))
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_class() {
+        let buf = build_ident(0xFF, ELFDATA2LSB, EV_CURRENT, ELFOSABI_SYSV, 0);
+        assert_eq!(parse_ident(&buf), Err(ElfError::UnknownClass(0xFF)));
+    }
+
+    #[test]
+    fn rejects_unknown_data() {
+        let buf = build_ident(ELFCLASS64, 0xFF, EV_CURRENT, ELFOSABI_SYSV, 0);
+        assert_eq!(parse_ident(&buf), Err(ElfError::UnknownData(0xFF)));
+    }
+
+    #[test]
+    fn rejects_unknown_version() {
+        let buf = build_ident(ELFCLASS64, ELFDATA2LSB, 0xFF, ELFOSABI_SYSV, 0);
+        assert_eq!(parse_ident(&buf), Err(ElfError::UnknownVersion(0xFF)));
+    }
+
+    #[test]
+    fn parses_a_valid_ident() {
+        let buf = build_ident(ELFCLASS32, ELFDATA2MSB, EV_CURRENT, ELFOSABI_LINUX, 1);
+        let ident = parse_ident(&buf).unwrap();
+        assert_eq!(ident.class, ElfClass::Elf32);
+        assert_eq!(ident.data, ElfData::Big);
+        assert_eq!(ident.version, EV_CURRENT);
+        assert_eq!(ident.os_abi, ELFOSABI_LINUX);
+        assert_eq!(ident.abi_version, 1);
+    }
+}
@@ -0,0 +1,17 @@
+//! Library support for parsing and inspecting ELF file headers.
+
+pub mod error;
+pub mod header;
+pub mod ident;
+pub mod machine;
+pub mod note;
+pub mod stub;
+pub mod verify;
+
+pub use error::ElfError;
+pub use header::{parse_header, parse_program_headers, ElfHeader, ProgramHeader};
+pub use ident::{parse_ident, ElfClass, ElfData, ElfIdent};
+pub use machine::machine_name;
+pub use note::{parse_abi_tags, AbiTag, AbiTagOs};
+pub use stub::ElfStub;
+pub use verify::{abi_recognized, Target};
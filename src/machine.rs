@@ -0,0 +1,46 @@
+//! `e_machine` values and their human-readable architecture names, similar to
+//! LLVM's `convertEMachineToArchName`.
+
+/// No machine.
+pub const EM_NONE: u16 = 0;
+
+/// Intel 80386.
+pub const EM_386: u16 = 3;
+
+/// PowerPC.
+pub const EM_PPC: u16 = 20;
+
+/// PowerPC64.
+pub const EM_PPC64: u16 = 21;
+
+/// MIPS.
+pub const EM_MIPS: u16 = 8;
+
+/// ARM.
+pub const EM_ARM: u16 = 40;
+
+/// AMD x86-64.
+pub const EM_X86_64: u16 = 62;
+
+/// AArch64.
+pub const EM_AARCH64: u16 = 183;
+
+/// RISC-V.
+pub const EM_RISCV: u16 = 243;
+
+/// Returns the human-readable architecture name for `e_machine`, or `None`
+/// if it is not one of the architectures this crate recognizes.
+pub fn machine_name(e_machine: u16) -> Option<&'static str> {
+    match e_machine {
+        EM_NONE => Some("None"),
+        EM_386 => Some("Intel 80386"),
+        EM_MIPS => Some("MIPS R3000"),
+        EM_PPC => Some("PowerPC"),
+        EM_PPC64 => Some("PowerPC64"),
+        EM_ARM => Some("ARM"),
+        EM_X86_64 => Some("Advanced Micro Devices X86-64"),
+        EM_AARCH64 => Some("AArch64"),
+        EM_RISCV => Some("RISC-V"),
+        _ => None,
+    }
+}
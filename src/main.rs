@@ -1,196 +1,385 @@
-use std::fs::File;
-use std::io::prelude::*;
-use std::path::PathBuf;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::str::FromStr;
 use structopt::StructOpt;
 
-/// The ELF magic number.
-pub const ELFMAG: &[u8; 4] = b"\x7FELF";
-
-/// Sizeof ELF magic number.
-pub const SELFMAG: usize = 4;
-
-/// Byte index identifies the architecture for this binary.
-pub const EI_CLASS: usize = 4;
-
-/// Class is invalid.
-pub const ELFCLASSNONE: u8 = 0;
+use elfrs::ident::{
+    ElfClass, ElfData, ELFOSABI_ARM, ELFOSABI_FREEBSD, ELFOSABI_LINUX, ELFOSABI_NETBSD,
+    ELFOSABI_SYSV, EI_ABIVERSION, EI_OSABI,
+};
+use elfrs::header::type_description;
+use elfrs::verify::rejection_reason;
+use elfrs::{
+    machine_name, parse_abi_tags, parse_header, parse_ident, parse_program_headers, ElfHeader,
+    ElfIdent, ElfStub, Target,
+};
+
+/// Output format for the `ident` subcommand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    /// The original free-text, line-per-field output.
+    Text,
+    Json,
+    Yaml,
+}
 
-/// This defines the 32-bit architecture.
-pub const ELFCLASS32: u8 = 1;
+impl FromStr for OutputFormat {
+    type Err = String;
 
-/// This defines the 64-bit architecture.
-pub const ELFCLASS64: u8 = 2;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "yaml" => Ok(OutputFormat::Yaml),
+            _ => Err(format!("unknown output format `{}`", s)),
+        }
+    }
+}
 
-/// Sizeof ELF e_ident array.
-pub const EI_NIDENT: usize = 16;
+#[derive(StructOpt, Debug)]
+#[structopt(name = "elfrs", about = "A simple ELF file parser tool.")]
+enum Opt {
+    /// Parse and print an ELF file's identification fields, optionally
+    /// re-branding its OS/ABI (brandelf-style)
+    Ident(IdentOpt),
 
-/// Byte index specifies the data encoding of the processor-specific data in the file.
-pub const EI_DATA: usize = 5;
+    /// Check whether a dynamic loader would accept this object's ABI identity
+    Verify(VerifyOpt),
 
-/// Unknown data format.
-pub const ELFDATANONE: u8 = 0;
+    /// Parse and print the full ELF header, readelf `-h`-style
+    Header(HeaderOpt),
 
-/// Two's complement, little-endian.
-pub const ELFDATA2LSB: u8 = 1;
+    /// Parse PT_NOTE segments and print any ABI-tag (NT_GNU_ABI_TAG) notes
+    Notes(NotesOpt),
+}
 
-/// Two's complement, big-endian.
-pub const ELFDATA2MSB: u8 = 2;
+#[derive(StructOpt, Debug)]
+struct IdentOpt {
+    /// Input ELF file
+    #[structopt(parse(from_os_str))]
+    input_elf: PathBuf,
 
-/// Byte index specifiies the version number of the ELF specification
-pub const EI_VERSION: usize = 6;
+    /// Output ELF file
+    #[structopt(parse(from_os_str))]
+    output_elf: PathBuf,
 
-/// Invalid version.
-pub const EV_NONE: u8 = 0;
+    /// Re-brand the output file with this OS/ABI (EI_OSABI). Accepts a
+    /// numeric value or a name such as FreeBSD, Linux/GNU, NetBSD, ARM, SYSV.
+    #[structopt(long, parse(try_from_str = parse_osabi))]
+    os_abi: Option<u8>,
 
-/// Current version (1).
-pub const EV_CURRENT: u8 = 1;
+    /// Re-brand the output file with this ABI version (EI_ABIVERSION).
+    #[structopt(long)]
+    abi_version: Option<u8>,
 
-/// Byte index identifies the operating system and ABI to which the object is targeted.  
-/// Some fields in other ELF structures have flags and values that have
-/// platform-specific meanings; the interpretation of those fields is determined by the value of this byte.
-pub const EI_OSABI: usize = 7;
+    /// Only print the parsed e_ident fields; never write `output_elf`. This
+    /// is the default when neither --os-abi nor --abi-version is given.
+    #[structopt(long)]
+    print: bool,
 
-/// Same as ELFOSABI_SYSV
-pub const ELFOSABI_NONE: u8 = 0;
+    /// Output format: text (default), json, or yaml. The json/yaml formats
+    /// emit a versioned, diffable ElfStub document instead of free text.
+    #[structopt(long, default_value = "text")]
+    format: OutputFormat,
+}
 
-/// UNIX System V ABI.
-pub const ELFOSABI_SYSV: u8 = 0;
+#[derive(StructOpt, Debug)]
+struct VerifyOpt {
+    /// Input ELF file
+    #[structopt(parse(from_os_str))]
+    input_elf: PathBuf,
 
-/// HP-UX ABI
-pub const ELFOSABI_HPUX: u8 = 1;
+    /// Target platform policy to verify against: generic, linux/gnu, arm.
+    #[structopt(long, default_value = "generic")]
+    target: Target,
+}
 
-/// NetBSD ABI
-pub const ELFOSABI_NETBSD: u8 = 2;
+#[derive(StructOpt, Debug)]
+struct HeaderOpt {
+    /// Input ELF file
+    #[structopt(parse(from_os_str))]
+    input_elf: PathBuf,
+}
 
-/// Linux ABI. Same as ELFOSABI_GNU.
-pub const ELFOSABI_LINUX: u8 = 3;
+#[derive(StructOpt, Debug)]
+struct NotesOpt {
+    /// Input ELF file
+    #[structopt(parse(from_os_str))]
+    input_elf: PathBuf,
+}
 
-/// Solaris ABI
-pub const ELFOSABI_SOLARIS: u8 = 6;
+/// Parse an `--os-abi` argument, accepting either a raw numeric value or one
+/// of the well-known names used by tools like `brandelf` and `elfedit`.
+fn parse_osabi(s: &str) -> Result<u8, String> {
+    if let Ok(n) = s.parse::<u8>() {
+        return Ok(n);
+    }
+    match s.to_ascii_uppercase().as_str() {
+        "SYSV" => Ok(ELFOSABI_SYSV),
+        "LINUX" | "GNU" => Ok(ELFOSABI_LINUX),
+        "FREEBSD" => Ok(ELFOSABI_FREEBSD),
+        "NETBSD" => Ok(ELFOSABI_NETBSD),
+        "ARM" => Ok(ELFOSABI_ARM),
+        _ => Err(format!("unknown OS/ABI name `{}`", s)),
+    }
+}
 
-/// AIX ABI
-pub const ELFOSABI_AIX: u8 = 7;
+/// Write `buf` to `output` byte-for-byte, overwriting only `e_ident[EI_OSABI]`
+/// and `e_ident[EI_ABIVERSION]` when the corresponding value is `Some`. This
+/// mirrors the FreeBSD `brandelf` and binutils `elfedit --output-abiversion`
+/// tools: the rest of the file, including the other e_ident bytes, is left
+/// untouched.
+///
+/// Takes the already-read, already-validated input buffer rather than
+/// re-reading the input path from disk, which would otherwise race with
+/// the file changing between the two reads.
+fn rewrite_osabi(
+    buf: &[u8],
+    output: &Path,
+    os_abi: Option<u8>,
+    abi_version: Option<u8>,
+) -> io::Result<()> {
+    if buf.len() <= EI_ABIVERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "file too short to hold e_ident",
+        ));
+    }
 
-/// IRIX ABI
-pub const ELFOSABI_IRIX: u8 = 8;
+    let mut buf = buf.to_vec();
+    if let Some(os_abi) = os_abi {
+        buf[EI_OSABI] = os_abi;
+    }
+    if let Some(abi_version) = abi_version {
+        buf[EI_ABIVERSION] = abi_version;
+    }
+    std::fs::write(output, buf)
+}
 
-/// FreeBSD ABI
-pub const ELFOSABI_FREEBSD: u8 = 9;
+fn print_ident(ident: &ElfIdent) {
+    match ident.class {
+        ElfClass::Elf64 => println!("ELF64"),
+        ElfClass::Elf32 => println!("ELF32"),
+    }
 
-/// Compaq TRU64 UNIX ABI
-pub const ELFOSABI_TRU64: u8 = 10;
+    match ident.data {
+        ElfData::Little => println!("2's complement, little-endian"),
+        ElfData::Big => println!("2's complement, big-endian"),
+    }
 
-/// Novell Modesto ABI
-pub const ELFOSABI_MODESTO: u8 = 11;
+    println!("{} (current)", ident.version);
 
-/// OpenBSD ABI
-pub const ELFOSABI_OPENBSD: u8 = 12;
+    match ident.os_abi {
+        ELFOSABI_SYSV => println!("UNIX - System V"),
+        ELFOSABI_LINUX => println!("Linux"),
+        other => println!("OS/ABI 0x{:x}", other),
+    }
 
-/// ARM EABI
-pub const ELFOSABI_ARM_AEABI: u8 = 64;
+    println!("{:x}", ident.abi_version);
+}
 
-/// ARM architecture ABI
-pub const ELFOSABI_ARM: u8 = 97;
+fn print_stub(opt: &IdentOpt, buf: &[u8], ident: &ElfIdent) -> Result<(), String> {
+    let stub = match parse_header(buf) {
+        Ok(header) => ElfStub::from_header(&header),
+        Err(_) => ElfStub::from_ident(ident),
+    };
+    let rendered = match opt.format {
+        OutputFormat::Json => {
+            serde_json::to_string_pretty(&stub).map_err(|e| e.to_string())?
+        }
+        OutputFormat::Yaml => serde_yaml::to_string(&stub).map_err(|e| e.to_string())?,
+        OutputFormat::Text => unreachable!("print_stub is only called for json/yaml formats"),
+    };
+    println!("{}", rendered);
+    Ok(())
+}
 
-/// Stand-alone (embedded) ABI
-pub const ELFOSABI_STANDALONE: u8 = 255;
+fn run_ident(opt: IdentOpt) -> Result<(), String> {
+    let buf = std::fs::read(&opt.input_elf).map_err(|e| format!("could not open file: {}", e))?;
+    let ident = parse_ident(&buf).map_err(|e| e.to_string())?;
 
-/// Byte index specifices the version of the ABI to which the object is targeted.
-/// This field is used to distinguish among incompatible versions of  an  ABI.
-/// The  interpretation  of  this version number is dependent on the ABI identified by the EI_OSABI field.
-/// Applications conforming to this specification use the value 0.
-pub const EI_ABIVERSION: usize = 8;
+    match opt.format {
+        OutputFormat::Text => {
+            println!("Parsing ELF file [{:?}]...", opt.input_elf);
+            print_ident(&ident);
+        }
+        OutputFormat::Json | OutputFormat::Yaml => print_stub(&opt, &buf, &ident)?,
+    }
 
-#[derive(StructOpt, Debug)]
-#[structopt(name = "elfrs", about = "A simple ELF file parser tool.")]
-struct Opt {
-    /// Input ELF file
-    #[structopt(parse(from_os_str))]
-    input_elf: PathBuf,
+    // Re-brand the output file if the caller asked for a different OS/ABI or
+    // ABI version. With neither flag (or --print) we stay read-only, which
+    // preserves the original behavior of this tool.
+    if !opt.print && (opt.os_abi.is_some() || opt.abi_version.is_some()) {
+        rewrite_osabi(&buf, &opt.output_elf, opt.os_abi, opt.abi_version)
+            .map_err(|e| format!("could not write output ELF file: {}", e))?;
+        println!("Wrote re-branded ELF file to [{:?}]", opt.output_elf);
+    }
 
-    /// Output ELF file
-    #[structopt(parse(from_os_str))]
-    output_elf: PathBuf,
+    Ok(())
 }
 
-fn main() {
-    let opt = Opt::from_args();
-    println!("Parsing ELF file [{:?}]...", opt.input_elf);
-
-    // Parse an ELF file
-    let mut fd = File::open(opt.input_elf).expect("could not open file");
-
-    // Read ELF magic and full ident array
-    let mut ident = [0_u8; EI_NIDENT];
-    let n = fd.read(&mut ident[..]).expect("something happened");
-
-    // Abort quickly if could not read e_ident or magic number is not valid
-    if n < EI_NIDENT {
-        panic!("Unexpected or malform ELF file.");
-    } else if n < SELFMAG {
-        panic!("Failed to read file's magic number.");
-    } else if &ident[..SELFMAG] != ELFMAG {
-        eprintln!("{:02X?}", &ident[..SELFMAG]);
-        panic!("Unknown or bad magic number.");
-    }
-
-    // Parse ELF class
-    let class = ident[EI_CLASS];
-    match class {
-        ELFCLASS64 => {
-            println!("ELF64");
+fn print_header(header: &ElfHeader) {
+    println!("ELF Header:");
+    println!(
+        "  Class:                             {}",
+        match header.ident.class {
+            ElfClass::Elf32 => "ELF32",
+            ElfClass::Elf64 => "ELF64",
         }
-        ELFCLASS32 => {
-            println!("ELF32");
+    );
+    println!(
+        "  Data:                              {}",
+        match header.ident.data {
+            ElfData::Little => "2's complement, little-endian",
+            ElfData::Big => "2's complement, big-endian",
         }
-        _ => {
-            panic!(format!("Invalid ELF class {:x}", class));
+    );
+    println!("  Version:                           {} (current)", header.ident.version);
+    println!(
+        "  OS/ABI:                            {}",
+        match header.ident.os_abi {
+            ELFOSABI_SYSV => "UNIX - System V".to_string(),
+            ELFOSABI_LINUX => "Linux".to_string(),
+            other => format!("0x{:x}", other),
         }
+    );
+    println!("  ABI Version:                       {}", header.ident.abi_version);
+    println!("  Type:                              {}", type_description(header.e_type));
+    println!(
+        "  Machine:                           {}",
+        machine_name(header.e_machine)
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("<unknown>: 0x{:x}", header.e_machine))
+    );
+    println!("  Version:                           0x{:x}", header.e_version);
+    println!("  Entry point address:               0x{:x}", header.e_entry);
+    println!(
+        "  Start of program headers:          {} (bytes into file)",
+        header.e_phoff
+    );
+    println!(
+        "  Start of section headers:          {} (bytes into file)",
+        header.e_shoff
+    );
+    println!("  Flags:                             0x{:x}", header.e_flags);
+    println!("  Size of this header:               {} (bytes)", header.e_ehsize);
+    println!(
+        "  Size of program headers:           {} (bytes)",
+        header.e_phentsize
+    );
+    println!("  Number of program headers:         {}", header.e_phnum);
+    println!(
+        "  Size of section headers:           {} (bytes)",
+        header.e_shentsize
+    );
+    println!("  Number of section headers:         {}", header.e_shnum);
+    println!("  Section header string table index: {}", header.e_shstrndx);
+}
+
+fn run_header(opt: HeaderOpt) -> Result<(), String> {
+    let buf = std::fs::read(&opt.input_elf).map_err(|e| format!("could not open file: {}", e))?;
+    let header = parse_header(&buf).map_err(|e| e.to_string())?;
+    print_header(&header);
+    Ok(())
+}
+
+fn run_notes(opt: NotesOpt) -> Result<(), String> {
+    let buf = std::fs::read(&opt.input_elf).map_err(|e| format!("could not open file: {}", e))?;
+    let header = parse_header(&buf).map_err(|e| e.to_string())?;
+    let phdrs = parse_program_headers(&buf, &header).map_err(|e| e.to_string())?;
+    let tags = parse_abi_tags(&buf, &phdrs, header.ident.data).map_err(|e| e.to_string())?;
+
+    if tags.is_empty() {
+        println!("{:?}: no ABI-tag notes found", opt.input_elf);
+        return Ok(());
     }
 
-    // Parsa Data encoding
-    let endianess = ident[EI_DATA];
-    match endianess {
-        ELFDATA2LSB => {
-            println!("2's complement, little-endian");
-        }
-        ELFDATA2MSB => {
-            println!("2's complement, big-endian");
-        }
-        _ => {
-            panic!(format!("Unknown ELF DATA format {:x}", endianess));
+    for tag in &tags {
+        let (major, minor, patch) = tag.min_kernel;
+        println!(
+            "ABI tag: {:?}, minimum kernel {}.{}.{}",
+            tag.os, major, minor, patch
+        );
+        if tag.conflicts_with_os_abi(header.ident.os_abi) {
+            println!(
+                "  warning: note claims {:?} but EI_OSABI is 0x{:x}",
+                tag.os, header.ident.os_abi
+            );
         }
     }
 
-    // Parse ELF Version
-    let version = ident[EI_VERSION];
-    match version {
-        EV_CURRENT => {
-            println!("1 (current)");
-        }
-        _ => {
-            panic!(format!("Unknown ELF Version `{:x}`", version));
+    Ok(())
+}
+
+fn run_verify(opt: VerifyOpt) -> Result<(), String> {
+    let buf = std::fs::read(&opt.input_elf).map_err(|e| format!("could not open file: {}", e))?;
+    let ident = parse_ident(&buf).map_err(|e| e.to_string())?;
+
+    match rejection_reason(&ident, opt.target) {
+        None => {
+            println!("{:?}: accepted for target {:?}", opt.input_elf, opt.target);
+            Ok(())
         }
+        Some(reason) => Err(format!(
+            "{:?}: rejected for target {:?}: {}",
+            opt.input_elf, opt.target, reason
+        )),
     }
+}
 
-    // Parse target OS / ABI
-    let osabi = ident[EI_OSABI];
-    match osabi {
-        ELFOSABI_SYSV => {
-            println!("UNIX - System V");
-        }
-        ELFOSABI_LINUX => {
-            println!("Linux");
-        }
-        _ => {
-            panic!(format!("OS/ABI Version `{:x}` not supported", osabi));
+fn run() -> Result<(), String> {
+    match Opt::from_args() {
+        Opt::Ident(opt) => run_ident(opt),
+        Opt::Verify(opt) => run_verify(opt),
+        Opt::Header(opt) => run_header(opt),
+        Opt::Notes(opt) => run_notes(opt),
+    }
+}
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("elfrs: {}", e);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewrite_osabi_only_touches_osabi_and_abiversion() {
+        let mut buf = [0xAB_u8; 32];
+        buf[EI_OSABI] = ELFOSABI_SYSV;
+        buf[EI_ABIVERSION] = 0;
+        let original = buf;
+
+        let output = std::env::temp_dir().join(format!(
+            "elfrs-test-rewrite-osabi-{:?}",
+            std::thread::current().id()
+        ));
+        rewrite_osabi(&buf, &output, Some(ELFOSABI_LINUX), Some(2)).unwrap();
+        let written = std::fs::read(&output).unwrap();
+        std::fs::remove_file(&output).unwrap();
+
+        assert_eq!(written.len(), original.len());
+        assert_eq!(written[EI_OSABI], ELFOSABI_LINUX);
+        assert_eq!(written[EI_ABIVERSION], 2);
+        for i in 0..original.len() {
+            if i != EI_OSABI && i != EI_ABIVERSION {
+                assert_eq!(written[i], original[i], "byte {} should be untouched", i);
+            }
         }
     }
 
-    // Parse ABI Version
-    let abi = ident[EI_ABIVERSION];
-    if abi != 0 {
-        panic!(format!("Extended ABI version `{:x}` not supported", abi));
+    #[test]
+    fn rewrite_osabi_rejects_a_buffer_too_short_for_e_ident() {
+        let output = std::env::temp_dir().join(format!(
+            "elfrs-test-rewrite-osabi-short-{:?}",
+            std::thread::current().id()
+        ));
+        let err = rewrite_osabi(&[0_u8; 4], &output, Some(ELFOSABI_LINUX), None).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
     }
-    println!("{:x}", abi);
 }
@@ -0,0 +1,215 @@
+//! Parsing of `PT_NOTE` segments, in particular the GNU/NetBSD/FreeBSD
+//! `NT_GNU_ABI_TAG` note. The FreeBSD/NetBSD/binutils branding history notes
+//! that this ABI note, rather than the `EI_OSABI` byte, is the more reliable
+//! way to identify an object's target OS and minimum kernel version.
+
+use crate::error::ElfError;
+use crate::header::{read_u32, ProgramHeader, PT_NOTE};
+use crate::ident::{
+    ElfData, ELFOSABI_FREEBSD, ELFOSABI_LINUX, ELFOSABI_NETBSD, ELFOSABI_SOLARIS, ELFOSABI_SYSV,
+};
+
+/// Records the minimum kernel ABI an object requires.
+pub const NT_GNU_ABI_TAG: u32 = 1;
+
+/// The operating system identified by an `NT_GNU_ABI_TAG` note's first word.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AbiTagOs {
+    Linux,
+    Hurd,
+    Solaris,
+    FreeBsd,
+    NetBsd,
+    Unknown(u32),
+}
+
+impl AbiTagOs {
+    fn from_raw(os: u32) -> Self {
+        match os {
+            0 => AbiTagOs::Linux,
+            1 => AbiTagOs::Hurd,
+            2 => AbiTagOs::Solaris,
+            3 => AbiTagOs::FreeBsd,
+            4 => AbiTagOs::NetBsd,
+            other => AbiTagOs::Unknown(other),
+        }
+    }
+}
+
+/// The decoded descriptor of an `NT_GNU_ABI_TAG` note.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AbiTag {
+    pub os: AbiTagOs,
+    pub min_kernel: (u32, u32, u32),
+}
+
+impl AbiTag {
+    /// Returns true if `os_abi` (the file's `EI_OSABI`) disagrees with the OS
+    /// this note claims to target. `ELFOSABI_SYSV` is treated as compatible
+    /// with any OS, since it is the common default for objects that never
+    /// set a specific OS/ABI byte. Notes naming GNU/Hurd or an unknown OS
+    /// have no `EI_OSABI` value to compare against and never conflict.
+    pub fn conflicts_with_os_abi(&self, os_abi: u8) -> bool {
+        let expected = match self.os {
+            AbiTagOs::Linux => ELFOSABI_LINUX,
+            AbiTagOs::Solaris => ELFOSABI_SOLARIS,
+            AbiTagOs::FreeBsd => ELFOSABI_FREEBSD,
+            AbiTagOs::NetBsd => ELFOSABI_NETBSD,
+            AbiTagOs::Hurd | AbiTagOs::Unknown(_) => return false,
+        };
+        os_abi != ELFOSABI_SYSV && os_abi != expected
+    }
+}
+
+/// Name strings recognized for `NT_GNU_ABI_TAG` notes, mirroring the GNU,
+/// FreeBSD, and NetBSD branding conventions.
+fn is_abi_tag_owner(name: &str) -> bool {
+    matches!(name, "GNU" | "FreeBSD" | "NetBSD")
+}
+
+fn align4(n: usize) -> usize {
+    (n + 3) & !3
+}
+
+/// Decode the note records packed into a single `PT_NOTE` segment's bytes.
+fn parse_notes(segment: &[u8], data: ElfData) -> Result<Vec<AbiTag>, ElfError> {
+    let mut tags = Vec::new();
+    let mut off = 0;
+
+    while off + 12 <= segment.len() {
+        let namesz = read_u32(segment, off, data)? as usize;
+        let descsz = read_u32(segment, off + 4, data)? as usize;
+        let n_type = read_u32(segment, off + 8, data)?;
+        off += 12;
+
+        let name = segment
+            .get(off..off + namesz)
+            .ok_or(ElfError::ShortRead)?;
+        let name = std::str::from_utf8(name)
+            .unwrap_or("")
+            .trim_end_matches('\0');
+        off += align4(namesz);
+
+        let desc = segment
+            .get(off..off + descsz)
+            .ok_or(ElfError::ShortRead)?;
+        off += align4(descsz);
+
+        if n_type == NT_GNU_ABI_TAG && is_abi_tag_owner(name) && desc.len() >= 16 {
+            tags.push(AbiTag {
+                os: AbiTagOs::from_raw(read_u32(desc, 0, data)?),
+                min_kernel: (
+                    read_u32(desc, 4, data)?,
+                    read_u32(desc, 8, data)?,
+                    read_u32(desc, 12, data)?,
+                ),
+            });
+        }
+    }
+
+    Ok(tags)
+}
+
+/// Walk every `PT_NOTE` segment in `phdrs` and decode the `NT_GNU_ABI_TAG`
+/// notes found within.
+pub fn parse_abi_tags(
+    buf: &[u8],
+    phdrs: &[ProgramHeader],
+    data: ElfData,
+) -> Result<Vec<AbiTag>, ElfError> {
+    let mut tags = Vec::new();
+
+    for phdr in phdrs.iter().filter(|p| p.p_type == PT_NOTE) {
+        let start = phdr.p_offset as usize;
+        let end = start
+            .checked_add(phdr.p_filesz as usize)
+            .ok_or(ElfError::ShortRead)?;
+        let segment = buf.get(start..end).ok_or(ElfError::ShortRead)?;
+        tags.extend(parse_notes(segment, data)?);
+    }
+
+    Ok(tags)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ident::ELFOSABI_FREEBSD;
+
+    /// Builds a single `NT_GNU_ABI_TAG` note record (name `"GNU"`, OS
+    /// `os`, minimum kernel `kernel`), little-endian.
+    fn build_abi_tag_note(os: u32, kernel: (u32, u32, u32)) -> Vec<u8> {
+        let name = b"GNU\0"; // namesz 4, already 4-byte aligned
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&(name.len() as u32).to_le_bytes()); // namesz
+        buf.extend_from_slice(&16_u32.to_le_bytes()); // descsz
+        buf.extend_from_slice(&NT_GNU_ABI_TAG.to_le_bytes()); // n_type
+        buf.extend_from_slice(name);
+        buf.extend_from_slice(&os.to_le_bytes());
+        buf.extend_from_slice(&kernel.0.to_le_bytes());
+        buf.extend_from_slice(&kernel.1.to_le_bytes());
+        buf.extend_from_slice(&kernel.2.to_le_bytes());
+        buf
+    }
+
+    fn note_phdr(offset: usize, len: usize) -> ProgramHeader {
+        ProgramHeader {
+            p_type: PT_NOTE,
+            p_flags: 0,
+            p_offset: offset as u64,
+            p_vaddr: 0,
+            p_paddr: 0,
+            p_filesz: len as u64,
+            p_memsz: len as u64,
+            p_align: 4,
+        }
+    }
+
+    #[test]
+    fn parse_abi_tags_decodes_a_synthetic_segment() {
+        let segment = build_abi_tag_note(0, (4, 4, 0)); // os=0 => Linux
+        let phdrs = [note_phdr(0, segment.len())];
+
+        let tags = parse_abi_tags(&segment, &phdrs, ElfData::Little).unwrap();
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags[0].os, AbiTagOs::Linux);
+        assert_eq!(tags[0].min_kernel, (4, 4, 0));
+    }
+
+    #[test]
+    fn parse_abi_tags_rejects_truncated_segment() {
+        // A full 12-byte note header claiming a 4-byte name, but the
+        // segment ends right after the header with no name bytes present.
+        let mut segment = Vec::new();
+        segment.extend_from_slice(&4_u32.to_le_bytes()); // namesz
+        segment.extend_from_slice(&16_u32.to_le_bytes()); // descsz
+        segment.extend_from_slice(&NT_GNU_ABI_TAG.to_le_bytes()); // n_type
+        let phdrs = [note_phdr(0, segment.len())];
+
+        assert_eq!(
+            parse_abi_tags(&segment, &phdrs, ElfData::Little),
+            Err(ElfError::ShortRead)
+        );
+    }
+
+    #[test]
+    fn parse_abi_tags_rejects_overflowing_offset() {
+        let phdrs = [note_phdr(usize::MAX - 1, 16)];
+
+        assert_eq!(
+            parse_abi_tags(&[0_u8; 16], &phdrs, ElfData::Little),
+            Err(ElfError::ShortRead)
+        );
+    }
+
+    #[test]
+    fn conflicts_with_os_abi_flags_mismatched_branding() {
+        let tag = AbiTag {
+            os: AbiTagOs::Linux,
+            min_kernel: (3, 2, 0),
+        };
+        assert!(tag.conflicts_with_os_abi(ELFOSABI_FREEBSD));
+        assert!(!tag.conflicts_with_os_abi(ELFOSABI_LINUX));
+        assert!(!tag.conflicts_with_os_abi(ELFOSABI_SYSV));
+    }
+}
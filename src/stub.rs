@@ -0,0 +1,112 @@
+//! A machine-readable "stub" document describing an ELF file's ABI identity,
+//! inspired by LLVM's interface-stub (TBE) text format. Meant to be diffed or
+//! compared across builds in scripts/CI, rather than scraped from free-text
+//! output.
+
+use serde::{Deserialize, Serialize};
+
+use crate::header::{type_description, ElfHeader};
+use crate::ident::{ElfClass, ElfData, ElfIdent, ELFOSABI_LINUX, ELFOSABI_SYSV};
+use crate::machine::machine_name;
+
+/// Schema version of [`ElfStub`], bumped whenever a field is added, renamed,
+/// or removed.
+pub const ELF_STUB_VERSION: u32 = 1;
+
+/// A versioned, round-trippable snapshot of an ELF file's ABI identity.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ElfStub {
+    pub stub_version: u32,
+    /// `"ELF32"` or `"ELF64"`.
+    pub class: String,
+    /// `"little"` or `"big"`.
+    pub data: String,
+    pub version: u8,
+    pub os_abi: String,
+    pub abi_version: u8,
+    /// Populated once the full ELF header has been parsed.
+    pub e_type: Option<String>,
+    pub machine: Option<String>,
+}
+
+fn os_abi_name(os_abi: u8) -> String {
+    match os_abi {
+        ELFOSABI_SYSV => "SYSV".to_string(),
+        ELFOSABI_LINUX => "Linux".to_string(),
+        other => format!("0x{:x}", other),
+    }
+}
+
+impl ElfStub {
+    /// Build a stub from just the `e_ident` array; `e_type` and `machine`
+    /// are left unset.
+    pub fn from_ident(ident: &ElfIdent) -> Self {
+        ElfStub {
+            stub_version: ELF_STUB_VERSION,
+            class: match ident.class {
+                ElfClass::Elf32 => "ELF32",
+                ElfClass::Elf64 => "ELF64",
+            }
+            .to_string(),
+            data: match ident.data {
+                ElfData::Little => "little",
+                ElfData::Big => "big",
+            }
+            .to_string(),
+            version: ident.version,
+            os_abi: os_abi_name(ident.os_abi),
+            abi_version: ident.abi_version,
+            e_type: None,
+            machine: None,
+        }
+    }
+
+    /// Build a stub from a fully parsed ELF header, including `e_type` and
+    /// the architecture name.
+    pub fn from_header(header: &ElfHeader) -> Self {
+        let mut stub = Self::from_ident(&header.ident);
+        stub.e_type = Some(type_description(header.e_type));
+        stub.machine = Some(
+            machine_name(header.e_machine)
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("0x{:x}", header.e_machine)),
+        );
+        stub
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ident::{ElfClass, ElfData, ElfIdent};
+
+    fn sample_ident() -> ElfIdent {
+        ElfIdent {
+            class: ElfClass::Elf64,
+            data: ElfData::Little,
+            version: 1,
+            os_abi: ELFOSABI_LINUX,
+            abi_version: 0,
+        }
+    }
+
+    #[test]
+    fn json_round_trips_through_elf_stub() {
+        let stub = ElfStub::from_ident(&sample_ident());
+
+        let json = serde_json::to_string(&stub).unwrap();
+        let decoded: ElfStub = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(decoded, stub);
+    }
+
+    #[test]
+    fn yaml_round_trips_through_elf_stub() {
+        let stub = ElfStub::from_ident(&sample_ident());
+
+        let yaml = serde_yaml::to_string(&stub).unwrap();
+        let decoded: ElfStub = serde_yaml::from_str(&yaml).unwrap();
+
+        assert_eq!(decoded, stub);
+    }
+}
@@ -0,0 +1,147 @@
+//! ABI-acceptance policy modeled on the checks a dynamic loader performs
+//! before it will run an object, e.g. glibc's `VALID_ELF_HEADER` /
+//! `VALID_ELF_OSABI` / `VALID_ELF_ABIVERSION` macros (and the older
+//! `ELF_ABI_RECOGNIZED`).
+
+use std::str::FromStr;
+
+use crate::ident::{ElfIdent, ELFOSABI_ARM, ELFOSABI_LINUX, ELFOSABI_SYSV};
+
+/// A target platform's dynamic-loader policy for which `EI_OSABI` values it
+/// recognizes. Every target accepts `ELFOSABI_SYSV`, since that is what a
+/// statically-linked or loader-agnostic object typically carries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    /// Generic policy: only `ELFOSABI_SYSV` is accepted.
+    Generic,
+    /// GNU/Linux: also accepts `ELFOSABI_GNU` (alias of `ELFOSABI_LINUX`).
+    Linux,
+    /// ARM: also accepts `ELFOSABI_ARM`.
+    Arm,
+}
+
+impl FromStr for Target {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "generic" | "sysv" => Ok(Target::Generic),
+            "linux" | "gnu" => Ok(Target::Linux),
+            "arm" => Ok(Target::Arm),
+            _ => Err(format!("unknown target `{}`", s)),
+        }
+    }
+}
+
+/// Returns true if a dynamic loader built for `target` would accept `ident`'s
+/// OS/ABI and ABI version.
+pub fn abi_recognized(ident: &ElfIdent, target: Target) -> bool {
+    if ident.abi_version != 0 {
+        return false;
+    }
+    match target {
+        Target::Generic => ident.os_abi == ELFOSABI_SYSV,
+        Target::Linux => ident.os_abi == ELFOSABI_SYSV || ident.os_abi == ELFOSABI_LINUX,
+        Target::Arm => ident.os_abi == ELFOSABI_SYSV || ident.os_abi == ELFOSABI_ARM,
+    }
+}
+
+/// Explains why `ident` was rejected for `target`, or `None` if it would be
+/// accepted.
+pub fn rejection_reason(ident: &ElfIdent, target: Target) -> Option<String> {
+    if abi_recognized(ident, target) {
+        return None;
+    }
+    if ident.abi_version != 0 {
+        return Some(format!(
+            "ABI version 0x{:x} not accepted (expected 0)",
+            ident.abi_version
+        ));
+    }
+    Some(format!(
+        "OS/ABI 0x{:x} not accepted for target {:?}",
+        ident.os_abi, target
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ident::{ElfClass, ElfData};
+
+    fn ident_with(os_abi: u8, abi_version: u8) -> ElfIdent {
+        ElfIdent {
+            class: ElfClass::Elf64,
+            data: ElfData::Little,
+            version: 1,
+            os_abi,
+            abi_version,
+        }
+    }
+
+    #[test]
+    fn target_from_str_accepts_known_aliases() {
+        assert_eq!("generic".parse(), Ok(Target::Generic));
+        assert_eq!("sysv".parse(), Ok(Target::Generic));
+        assert_eq!("linux".parse(), Ok(Target::Linux));
+        assert_eq!("GNU".parse(), Ok(Target::Linux));
+        assert_eq!("arm".parse(), Ok(Target::Arm));
+        assert_eq!("ARM".parse(), Ok(Target::Arm));
+    }
+
+    #[test]
+    fn target_from_str_rejects_unknown_name() {
+        assert_eq!(
+            "wasm".parse::<Target>(),
+            Err("unknown target `wasm`".to_string())
+        );
+    }
+
+    #[test]
+    fn generic_only_accepts_sysv() {
+        assert!(abi_recognized(&ident_with(ELFOSABI_SYSV, 0), Target::Generic));
+        assert!(!abi_recognized(&ident_with(ELFOSABI_LINUX, 0), Target::Generic));
+        assert!(!abi_recognized(&ident_with(ELFOSABI_ARM, 0), Target::Generic));
+    }
+
+    #[test]
+    fn linux_accepts_sysv_and_linux_but_not_arm() {
+        assert!(abi_recognized(&ident_with(ELFOSABI_SYSV, 0), Target::Linux));
+        assert!(abi_recognized(&ident_with(ELFOSABI_LINUX, 0), Target::Linux));
+        assert!(!abi_recognized(&ident_with(ELFOSABI_ARM, 0), Target::Linux));
+    }
+
+    #[test]
+    fn arm_accepts_sysv_and_arm_but_not_linux() {
+        assert!(abi_recognized(&ident_with(ELFOSABI_SYSV, 0), Target::Arm));
+        assert!(abi_recognized(&ident_with(ELFOSABI_ARM, 0), Target::Arm));
+        assert!(!abi_recognized(&ident_with(ELFOSABI_LINUX, 0), Target::Arm));
+    }
+
+    #[test]
+    fn nonzero_abi_version_is_rejected_regardless_of_target() {
+        assert!(!abi_recognized(&ident_with(ELFOSABI_SYSV, 1), Target::Generic));
+        assert!(!abi_recognized(&ident_with(ELFOSABI_SYSV, 1), Target::Linux));
+        assert!(!abi_recognized(&ident_with(ELFOSABI_SYSV, 1), Target::Arm));
+    }
+
+    #[test]
+    fn rejection_reason_is_none_when_accepted() {
+        assert_eq!(
+            rejection_reason(&ident_with(ELFOSABI_SYSV, 0), Target::Generic),
+            None
+        );
+    }
+
+    #[test]
+    fn rejection_reason_explains_bad_abi_version() {
+        let reason = rejection_reason(&ident_with(ELFOSABI_SYSV, 1), Target::Generic).unwrap();
+        assert!(reason.contains("ABI version"));
+    }
+
+    #[test]
+    fn rejection_reason_explains_bad_os_abi() {
+        let reason = rejection_reason(&ident_with(ELFOSABI_LINUX, 0), Target::Generic).unwrap();
+        assert!(reason.contains("OS/ABI"));
+    }
+}